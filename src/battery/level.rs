@@ -1,3 +1,4 @@
+use super::percentage::ChargingState;
 use super::status::BatteryStatus;
 
 /// Describes roughly the amount of charge in a battery.
@@ -24,7 +25,11 @@ impl BatteryLevel {
     /// it is **allowed** to transition from and to any `BatteryLevel`,
     /// and this function just gives a possible `BatteryStatus` to
     /// describe the transition.
-    pub fn transition(&self, to: &Self) -> Option<BatteryStatus> {
+    ///
+    /// `state` reports the real direction of charge. When it is
+    /// `Unknown` (e.g. the system does not expose it) the decision falls
+    /// back to the transition direction alone.
+    pub fn transition(&self, to: &Self, state: ChargingState) -> Option<BatteryStatus> {
         if self == to {
             return None;
         }
@@ -32,6 +37,8 @@ impl BatteryLevel {
         match (self, to) {
             (_, Self::Unknown) => Some(BatteryStatus::Unknown),
 
+            // A critical charge is not alarming while plugged in.
+            (_, Self::Critical) if state == ChargingState::Charging => None,
             (_, Self::Critical) => Some(BatteryStatus::Critical),
 
             (Self::Critical, Self::Low) => None,
@@ -40,6 +47,12 @@ impl BatteryLevel {
             (Self::Full, Self::High) => None,
             (_, Self::High) => Some(BatteryStatus::High),
 
+            // A full charge is only announced once the status confirms it.
+            (_, Self::Full)
+                if state != ChargingState::Full && state != ChargingState::Unknown =>
+            {
+                None
+            }
             (_, Self::Full) => Some(BatteryStatus::Full),
 
             _ => None,
@@ -53,8 +66,13 @@ mod tests {
 
     use BatteryLevel::*;
 
-    fn test_transition(from: &BatteryLevel, to: &BatteryLevel, expected: Option<BatteryStatus>) {
-        assert_eq!(from.transition(to), expected);
+    fn test_transition(
+        from: &BatteryLevel,
+        to: &BatteryLevel,
+        state: ChargingState,
+        expected: Option<BatteryStatus>,
+    ) {
+        assert_eq!(from.transition(to, state), expected);
     }
 
     /// Should always give `None`.
@@ -62,7 +80,7 @@ mod tests {
     fn self_transition() {
         let levels = [&Unknown, &Critical, &Low, &Regular, &High, &Full];
         for level in levels {
-            test_transition(level, level, None);
+            test_transition(level, level, ChargingState::Unknown, None);
         }
     }
 
@@ -78,7 +96,7 @@ mod tests {
         ];
 
         for (from, to, expected) in entries {
-            test_transition(from, to, expected);
+            test_transition(from, to, ChargingState::Unknown, expected);
         }
     }
 
@@ -94,7 +112,37 @@ mod tests {
         ];
 
         for (from, to, expected) in entries {
-            test_transition(from, to, expected);
+            test_transition(from, to, ChargingState::Unknown, expected);
         }
     }
+
+    /// Should suppress the critical alarm while actually charging.
+    #[test]
+    fn charging_suppresses_critical() {
+        test_transition(&Low, &Critical, ChargingState::Charging, None);
+        test_transition(
+            &Low,
+            &Critical,
+            ChargingState::Discharging,
+            Some(BatteryStatus::Critical),
+        );
+    }
+
+    /// Should only announce `Full` once the status confirms it.
+    #[test]
+    fn full_requires_full_status() {
+        test_transition(&High, &Full, ChargingState::Charging, None);
+        test_transition(
+            &High,
+            &Full,
+            ChargingState::Full,
+            Some(BatteryStatus::Full),
+        );
+        test_transition(
+            &High,
+            &Full,
+            ChargingState::Unknown,
+            Some(BatteryStatus::Full),
+        );
+    }
 }
@@ -1,5 +1,7 @@
 use std::{fmt, fs};
 
+use serde::Deserialize;
+
 use crate::error::{Error, Result};
 use crate::validate;
 
@@ -17,6 +19,11 @@ impl Percentage {
         Ok(Self(val))
     }
 
+    /// The charge as a plain number.
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+
     /// Gets the current battery charge from a system file.
     pub fn open_and_parse_file(path: &str) -> Result<Self> {
         let contents = fs::read_to_string(path)?;
@@ -30,6 +37,68 @@ impl fmt::Debug for Percentage {
     }
 }
 
+/// The direction in which the battery charge is moving.
+///
+/// Read from the sysfs `status` file, which holds one of `Charging`,
+/// `Discharging`, `Full`, `Not charging` or `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargingState {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl ChargingState {
+    /// Gets the current charging state from a system file.
+    pub fn open_and_parse_file(path: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Ok(Self::from(contents.trim()))
+    }
+}
+
+impl From<&str> for ChargingState {
+    fn from(s: &str) -> Self {
+        match s {
+            "Charging" => Self::Charging,
+            "Discharging" => Self::Discharging,
+            "Full" => Self::Full,
+            "Not charging" => Self::NotCharging,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod charging_state_tests {
+    use super::ChargingState;
+
+    /// Should map the known sysfs strings.
+    #[test]
+    fn from_str_known() {
+        let entries = [
+            ("Charging", ChargingState::Charging),
+            ("Discharging", ChargingState::Discharging),
+            ("Full", ChargingState::Full),
+            ("Not charging", ChargingState::NotCharging),
+        ];
+
+        for (s, expected) in entries {
+            assert_eq!(ChargingState::from(s), expected);
+        }
+    }
+
+    /// Should fall back to `Unknown` for anything else.
+    #[test]
+    fn from_str_unknown() {
+        let vals = ["", "Unknown", "charging", "foo"];
+        for val in vals {
+            assert_eq!(ChargingState::from(val), ChargingState::Unknown);
+        }
+    }
+}
+
 impl TryFrom<String> for Percentage {
     type Error = Error;
 
@@ -80,6 +149,77 @@ mod tests {
     }
 }
 
+/// How to reduce several batteries into a single `Percentage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregationPolicy {
+    /// Sum of the charges, clamped to the maximum.
+    Sum,
+    /// Rounded mean of the charges.
+    Average,
+    /// The emptiest battery.
+    Min,
+}
+
+impl Default for AggregationPolicy {
+    fn default() -> Self {
+        Self::Average
+    }
+}
+
+impl AggregationPolicy {
+    /// Reduces the per-battery charges, or `None` when there are none.
+    pub fn reduce(&self, values: &[u8]) -> Option<u8> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let total: u32 = values.iter().map(|v| u32::from(*v)).sum();
+
+        Some(match self {
+            Self::Sum => total.min(u32::from(Percentage::MAX)) as u8,
+            Self::Average => {
+                let len = values.len() as u32;
+                ((total + len / 2) / len) as u8
+            }
+            Self::Min => *values.iter().min().unwrap(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod aggregation_policy_tests {
+    use super::AggregationPolicy;
+
+    #[test]
+    fn reduce() {
+        let entries = [
+            (AggregationPolicy::Sum, &[60, 30][..], Some(90)),
+            (AggregationPolicy::Sum, &[80, 70][..], Some(100)),
+            (AggregationPolicy::Average, &[60, 30][..], Some(45)),
+            (AggregationPolicy::Average, &[60, 31][..], Some(46)),
+            (AggregationPolicy::Min, &[60, 30][..], Some(30)),
+        ];
+
+        for (policy, values, expected) in entries {
+            assert_eq!(policy.reduce(values), expected);
+        }
+    }
+
+    #[test]
+    fn reduce_empty() {
+        let policies = [
+            AggregationPolicy::Sum,
+            AggregationPolicy::Average,
+            AggregationPolicy::Min,
+        ];
+
+        for policy in policies {
+            assert_eq!(policy.reduce(&[]), None);
+        }
+    }
+}
+
 /// Groups `Percentage` marks to select a `BatteryLevel`.
 #[derive(Debug)]
 pub struct Breakpoints {
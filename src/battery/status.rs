@@ -1,7 +1,11 @@
+use std::collections::HashMap;
 use std::fmt;
+use std::time::Duration;
 
 use notify_rust::{Notification, Urgency};
 
+use crate::utils;
+
 /// Each `BatteryStatus` produces a `Notification`.
 #[derive(Debug, PartialEq)]
 pub enum BatteryStatus {
@@ -24,6 +28,94 @@ impl fmt::Display for BatteryStatus {
     }
 }
 
+/// Per-level overrides for the produced `Notification`.
+#[derive(Debug, Clone)]
+pub struct LevelStyle {
+    pub urgency: Urgency,
+    pub timeout: i32,
+    pub summary: Option<String>,
+    pub body: Option<String>,
+    pub icon: Option<String>,
+}
+
+/// Maps each `BatteryStatus` to its `LevelStyle`, keyed by the status name.
+#[derive(Debug, Default)]
+pub struct Display {
+    styles: HashMap<String, LevelStyle>,
+}
+
+impl Display {
+    /// Registers the style for a level (`critical`, `low`, `high`, `full`, `unknown`).
+    pub fn insert(&mut self, key: String, style: LevelStyle) {
+        self.styles.insert(key, style);
+    }
+
+    fn get(&self, key: &str) -> Option<&LevelStyle> {
+        self.styles.get(key)
+    }
+}
+
+impl BatteryStatus {
+    /// The key used to look this status up in a `Display`.
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Unknown(_) => "unknown",
+            Self::Critical => "critical",
+            Self::Low => "low",
+            Self::High => "high",
+            Self::Full => "full",
+        }
+    }
+
+    /// Builds the `Notification`, applying per-level `Display` overrides and
+    /// appending an estimated remaining time to the body.
+    ///
+    /// The estimate is skipped when it is absent or when the body already
+    /// carries an error message.
+    pub fn into_notification(self, display: &Display, remaining: Option<Duration>) -> Notification {
+        let style = display.get(self.key()).cloned();
+        let has_error_body = matches!(self, Self::Unknown(Some(_)));
+        let mut notification = Notification::from(self);
+
+        // A configured body (and the estimate below) must never clobber the
+        // real error message carried by an `Unknown(Some(_))` status.
+        let custom_body = if has_error_body {
+            None
+        } else {
+            style.as_ref().and_then(|style| style.body.clone())
+        };
+
+        if let Some(style) = &style {
+            notification.urgency(style.urgency).timeout(style.timeout);
+            if let Some(summary) = &style.summary {
+                notification.summary(summary);
+            }
+            if let Some(icon) = &style.icon {
+                notification.icon(icon);
+            }
+        }
+
+        let estimate = remaining
+            .filter(|_| !has_error_body)
+            .map(|remaining| utils::format_duration(remaining));
+
+        match (custom_body, estimate) {
+            (Some(body), Some(estimate)) => {
+                notification.body(&format!("{body} ({estimate} remaining)"));
+            }
+            (Some(body), None) => {
+                notification.body(&body);
+            }
+            (None, Some(estimate)) => {
+                notification.body(&format!("about {estimate} remaining"));
+            }
+            (None, None) => {}
+        }
+
+        notification
+    }
+}
+
 impl From<BatteryStatus> for Notification {
     fn from(status: BatteryStatus) -> Self {
         const TIMEOUT: i32 = 5000;
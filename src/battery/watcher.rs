@@ -1,56 +1,402 @@
+use std::collections::VecDeque;
 use std::iter;
-use std::time::Duration;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
 
-use notify_rust::Notification;
+use std::collections::HashMap;
+use std::fs;
 
-use crate::error::Result;
+use notify_rust::{Notification, Urgency};
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
 use crate::utils;
 use crate::validate;
 
 use super::level::BatteryLevel;
-use super::percentage::{Breakpoints, Percentage};
-use super::status::BatteryStatus;
+use super::percentage::{AggregationPolicy, Breakpoints, ChargingState, Percentage};
+use super::status::{BatteryStatus, Display, LevelStyle};
+
+/// A single reading from a battery source.
+#[derive(Debug)]
+pub struct BatteryReading {
+    pub percentage: Percentage,
+    pub charging_state: ChargingState,
+}
+
+/// Something that can report the current battery charge.
+///
+/// Decoupling the `Watcher` from the filesystem keeps the state machine
+/// testable with a scripted `MockSource` and leaves room for an alternate
+/// cross-platform backend built on the `battery` crate.
+pub trait BatterySource {
+    /// Reads the current charge and, when available, its direction.
+    fn read(&self) -> Result<BatteryReading>;
+}
+
+/// A single battery pack exposed through sysfs.
+#[derive(Debug)]
+struct SysfsBattery {
+    capacity_file: String,
+    status_file: Option<String>,
+}
+
+impl SysfsBattery {
+    fn new(capacity_file: String) -> Result<Self> {
+        let capacity_file = validate::required("battery_file", capacity_file)?;
+
+        // The `status` file sits next to `capacity` in the same sysfs directory.
+        let status_file = Path::new(&capacity_file)
+            .parent()
+            .map(|dir| dir.join("status").to_string_lossy().into_owned());
+
+        Ok(Self {
+            capacity_file,
+            status_file,
+        })
+    }
+
+    fn read_percentage(&self) -> Result<Percentage> {
+        Percentage::open_and_parse_file(&self.capacity_file)
+    }
+
+    /// Gets the current charging state.
+    ///
+    /// Not every system exposes the `status` file; when it is missing the
+    /// state is reported as `Unknown` so the transition falls back to the
+    /// charge-direction heuristic.
+    fn read_charging_state(&self) -> ChargingState {
+        match &self.status_file {
+            Some(path) => {
+                ChargingState::open_and_parse_file(path).unwrap_or(ChargingState::Unknown)
+            }
+            None => ChargingState::Unknown,
+        }
+    }
+}
+
+/// Reads the battery charge from the Linux sysfs files.
+///
+/// Several packs are reduced into a single charge through an
+/// [`AggregationPolicy`], so multi-battery laptops report every pack
+/// rather than only one.
+#[derive(Debug)]
+pub struct SysfsSource {
+    batteries: Vec<SysfsBattery>,
+    policy: AggregationPolicy,
+}
+
+impl SysfsSource {
+    /// Watches a single capacity file with the default policy.
+    pub fn new(capacity_file: String) -> Result<Self> {
+        Self::with_policy(vec![capacity_file], AggregationPolicy::default())
+    }
+
+    /// Watches the given capacity files, reducing them with `policy`.
+    pub fn with_policy(capacity_files: Vec<String>, policy: AggregationPolicy) -> Result<Self> {
+        let batteries = capacity_files
+            .into_iter()
+            .map(SysfsBattery::new)
+            .collect::<Result<Vec<_>>>()?;
+
+        validate::not_empty("battery_file", &batteries)?;
+
+        Ok(Self { batteries, policy })
+    }
+
+    /// Discovers every `/sys/class/power_supply/BAT*/capacity` file.
+    pub fn discover(policy: AggregationPolicy) -> Result<Self> {
+        const DIR: &str = "/sys/class/power_supply";
+
+        let mut capacity_files: Vec<String> = fs::read_dir(DIR)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+            .map(|entry| entry.path().join("capacity").to_string_lossy().into_owned())
+            .collect();
+
+        // A stable order keeps the aggregated reading reproducible.
+        capacity_files.sort();
+
+        Self::with_policy(capacity_files, policy)
+    }
+}
+
+impl BatterySource for SysfsSource {
+    fn read(&self) -> Result<BatteryReading> {
+        let mut values = Vec::with_capacity(self.batteries.len());
+        let mut charging_state = ChargingState::Unknown;
+
+        for battery in &self.batteries {
+            values.push(battery.read_percentage()?.value());
+
+            // A single discharging pack drives the whole machine down.
+            let state = battery.read_charging_state();
+            if charging_state == ChargingState::Unknown || state == ChargingState::Discharging {
+                charging_state = state;
+            }
+        }
+
+        let aggregated = self
+            .policy
+            .reduce(&values)
+            .expect("at least one battery is enforced at construction");
+
+        Ok(BatteryReading {
+            percentage: Percentage::new(aggregated)?,
+            charging_state,
+        })
+    }
+}
+
+/// An action to take when the battery reaches a critical charge.
+#[derive(Debug)]
+pub enum CriticalAction {
+    /// Spawn a user-configured shell command.
+    Command(String),
+    /// Suspend the system through `systemctl suspend`.
+    Suspend,
+}
+
+impl CriticalAction {
+    /// Fires the action once.
+    ///
+    /// The child process is spawned without waiting for it; a failure to
+    /// spawn is surfaced as an `Error` rather than a panic.
+    pub fn fire(&self) -> Result<()> {
+        let mut command = match self {
+            Self::Command(cmd) => {
+                let mut command = Command::new("sh");
+                command.arg("-c").arg(cmd);
+                command
+            }
+            Self::Suspend => {
+                let mut command = Command::new("systemctl");
+                command.arg("suspend");
+                command
+            }
+        };
+
+        command.spawn()?;
+        Ok(())
+    }
+}
 
 /// Contains settings for `Watcher`.
 #[derive(Debug)]
 pub struct Config {
-    battery_file: String,
     breakpoints: Breakpoints,
     interval: Duration,
+    critical_action: Option<CriticalAction>,
+    display: Display,
 }
 
 impl Config {
-    pub fn new(battery_file: String, breakpoints: Breakpoints, interval: Duration) -> Result<Self> {
-        let battery_file = validate::required("battery_file", battery_file)?;
+    pub fn new(breakpoints: Breakpoints, interval: Duration) -> Result<Self> {
         validate::greater_than_zero(interval)?;
 
         Ok(Self {
-            battery_file,
             breakpoints,
             interval,
+            critical_action: None,
+            display: Display::default(),
         })
     }
 
-    /// Gets the current battery charge.
-    pub fn read_percentage(&self) -> Result<Percentage> {
-        Percentage::open_and_parse_file(&self.battery_file)
+    /// Sets the action fired when the battery enters the critical state while discharging.
+    pub fn set_critical_action(&mut self, action: CriticalAction) {
+        self.critical_action = Some(action);
+    }
+
+    /// Sets the per-level notification styling.
+    pub fn set_display(&mut self, display: Display) {
+        self.display = display;
+    }
+}
+
+/// Mirrors the on-disk TOML configuration.
+#[derive(Debug, Deserialize)]
+struct FileConfig {
+    #[serde(default)]
+    batteries: Vec<String>,
+    interval: Option<u64>,
+    policy: Option<AggregationPolicy>,
+    breakpoints: Option<FileBreakpoints>,
+    #[serde(default)]
+    display: HashMap<String, FileLevelStyle>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileBreakpoints {
+    critical: u8,
+    low: u8,
+    high: u8,
+    full: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileLevelStyle {
+    urgency: Option<String>,
+    timeout: Option<i32>,
+    summary: Option<String>,
+    body: Option<String>,
+    icon: Option<String>,
+}
+
+impl FileLevelStyle {
+    /// The default notification timeout, in milliseconds.
+    const DEFAULT_TIMEOUT: i32 = 5000;
+
+    fn into_style(self) -> Result<LevelStyle> {
+        let urgency = match self.urgency.as_deref() {
+            None | Some("normal") => Urgency::Normal,
+            Some("low") => Urgency::Low,
+            Some("critical") => Urgency::Critical,
+            Some(other) => {
+                return Err(Error::Config(format!("unknown urgency '{other}'")));
+            }
+        };
+
+        Ok(LevelStyle {
+            urgency,
+            timeout: self.timeout.unwrap_or(Self::DEFAULT_TIMEOUT),
+            summary: self.summary,
+            body: self.body,
+            icon: self.icon,
+        })
+    }
+}
+
+/// Loads a `Config` and its battery `SysfsSource` from a TOML file.
+///
+/// The breakpoints are validated with the same `validate` checks as the
+/// built-in defaults, so an invalid on-disk config yields a clear `Error`
+/// instead of a panic.
+pub fn load(path: &str) -> Result<(Config, SysfsSource)> {
+    let contents = fs::read_to_string(path)?;
+    let file: FileConfig = toml::from_str(&contents)?;
+
+    let breakpoints = match file.breakpoints {
+        Some(b) => Breakpoints::new(b.critical, b.low, b.high, b.full)?,
+        None => Breakpoints::new(10, 13, 94, 97)?,
+    };
+
+    let interval = Duration::from_secs(file.interval.unwrap_or(45));
+    let policy = file.policy.unwrap_or_default();
+
+    let source = if file.batteries.is_empty() {
+        SysfsSource::discover(policy)?
+    } else {
+        SysfsSource::with_policy(file.batteries, policy)?
+    };
+
+    let mut display = Display::default();
+    for (key, style) in file.display {
+        display.insert(key, style.into_style()?);
+    }
+
+    let mut config = Config::new(breakpoints, interval)?;
+    config.set_display(display);
+
+    Ok((config, source))
+}
+
+/// Estimates the remaining time from recent charge samples.
+///
+/// It keeps a small ring buffer of `(Instant, percentage)` readings and
+/// extrapolates the average slope linearly to 0% (discharging) or 100%
+/// (charging). Samples older than `max_age` are discarded so a long
+/// sleep/resume does not poison the slope.
+#[derive(Debug)]
+struct Estimator {
+    samples: VecDeque<(Instant, u8)>,
+    max_age: Duration,
+}
+
+impl Estimator {
+    const WINDOW: usize = 8;
+
+    /// Minimum magnitude of the slope, in percent per second. Anything
+    /// shallower would extrapolate to an implausible figure (beyond roughly
+    /// a day), so it is treated as no estimate at all.
+    const MIN_SLOPE: f64 = 0.001;
+
+    fn new(max_age: Duration) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            max_age,
+        }
+    }
+
+    /// Records a new sample, dropping stale and overflowing ones.
+    fn push(&mut self, now: Instant, percentage: u8) {
+        while let Some((instant, _)) = self.samples.front() {
+            if now.duration_since(*instant) > self.max_age {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        self.samples.push_back((now, percentage));
+
+        while self.samples.len() > Self::WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Extrapolates the remaining time for the given direction of charge.
+    ///
+    /// Returns `None` when the slope is too shallow to be meaningful or
+    /// points the wrong way, so a flat battery or one that just changed
+    /// direction emits no estimate instead of an absurd multi-day figure.
+    fn estimate(&self, state: ChargingState) -> Option<Duration> {
+        let (first_instant, first) = self.samples.front()?;
+        let (last_instant, last) = self.samples.back()?;
+
+        let elapsed = last_instant.duration_since(*first_instant).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+
+        let slope = (f64::from(*last) - f64::from(*first)) / elapsed;
+        if first == last || slope.abs() < Self::MIN_SLOPE {
+            return None;
+        }
+
+        let remaining = match state {
+            ChargingState::Discharging if slope < 0.0 => f64::from(*last),
+            ChargingState::Charging if slope > 0.0 => 100.0 - f64::from(*last),
+            _ => return None,
+        };
+
+        Some(Duration::from_secs_f64((remaining / slope).abs()))
     }
 }
 
 /// Keeps track of the `BatteryLevel`.
 #[derive(Debug)]
-pub struct Watcher {
+pub struct Watcher<S> {
     config: Config,
+    source: S,
     state: BatteryLevel,
+    charging_state: ChargingState,
+    estimator: Estimator,
 }
 
-impl Watcher {
-    pub fn new(config: Config) -> Self {
+impl<S: BatterySource> Watcher<S> {
+    pub fn new(config: Config, source: S) -> Self {
         eprintln!("config = {:#?}", config);
 
+        // Keep a handful of polling intervals worth of samples.
+        let estimator = Estimator::new(config.interval * 5);
+
         Self {
             config,
+            source,
             state: BatteryLevel::default(),
+            charging_state: ChargingState::Unknown,
+            estimator,
         }
     }
 
@@ -66,17 +412,37 @@ impl Watcher {
 
     /// Gets a new `BatteryLevel` to update the internal state and produce a `BatteryStatus`.
     fn update(&mut self) -> Result<Option<BatteryStatus>> {
-        match self.config.read_percentage() {
-            Ok(percentage) => {
-                let level = self.config.breakpoints.get_level(&percentage);
-                let status = self.state.transition(&level);
+        match self.source.read() {
+            Ok(reading) => {
+                let level = self.config.breakpoints.get_level(&reading.percentage);
+                let status = self.state.transition(&level, reading.charging_state);
+
+                self.charging_state = reading.charging_state;
+                self.estimator
+                    .push(Instant::now(), reading.percentage.value());
 
                 eprintln!(
                     "percentage: {:?}, level: {:?}, status: {:?}",
-                    percentage, level, status
+                    reading.percentage, level, status
                 );
 
                 self.state = level;
+
+                // `transition` only yields `Critical` on entry into the
+                // state, so the action is naturally debounced to once per
+                // entry. Confirm the battery is actually draining (or the
+                // direction is unknown) before acting. A spawn failure
+                // surfaces through the `Error` path.
+                let draining = matches!(
+                    reading.charging_state,
+                    ChargingState::Discharging | ChargingState::Unknown
+                );
+                if draining && status == Some(BatteryStatus::Critical) {
+                    if let Some(action) = &self.config.critical_action {
+                        action.fire()?;
+                    }
+                }
+
                 Ok(status)
             }
             Err(e) => {
@@ -87,13 +453,132 @@ impl Watcher {
     }
 }
 
-impl iter::Iterator for Watcher {
+impl<S: BatterySource> iter::Iterator for Watcher<S> {
     type Item = Option<Notification>;
 
     fn next(&mut self) -> Option<Self::Item> {
         Some(match self.update() {
-            Ok(status) => status.and_then(|s| Some(s.into())),
+            Ok(status) => {
+                let remaining = self.estimator.estimate(self.charging_state);
+                status.map(|s| s.into_notification(&self.config.display, remaining))
+            }
             Err(e) => Some(e.into()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io;
+    use std::vec;
+
+    use super::*;
+
+    /// A `BatterySource` that yields a scripted sequence of readings.
+    struct MockSource {
+        readings: RefCell<vec::IntoIter<BatteryReading>>,
+    }
+
+    impl MockSource {
+        fn new(readings: Vec<BatteryReading>) -> Self {
+            Self {
+                readings: RefCell::new(readings.into_iter()),
+            }
+        }
+    }
+
+    impl BatterySource for MockSource {
+        fn read(&self) -> Result<BatteryReading> {
+            self.readings
+                .borrow_mut()
+                .next()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "no more readings").into())
+        }
+    }
+
+    fn reading(percentage: u8, charging_state: ChargingState) -> BatteryReading {
+        BatteryReading {
+            percentage: Percentage::new(percentage).unwrap(),
+            charging_state,
+        }
+    }
+
+    fn watcher(readings: Vec<BatteryReading>) -> Watcher<MockSource> {
+        let config = Config::new(
+            Breakpoints::new(10, 13, 94, 97).unwrap(),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        Watcher::new(config, MockSource::new(readings))
+    }
+
+    /// Should only emit a `BatteryStatus` when the level changes.
+    #[test]
+    fn emits_on_level_change() {
+        let mut watcher = watcher(vec![
+            reading(50, ChargingState::Discharging),
+            reading(48, ChargingState::Discharging),
+            reading(5, ChargingState::Discharging),
+        ]);
+
+        assert_eq!(watcher.update().unwrap(), None);
+        assert_eq!(watcher.update().unwrap(), None);
+        assert_eq!(watcher.update().unwrap(), Some(BatteryStatus::Critical));
+    }
+
+    /// Should report `Err` once the scripted readings are exhausted.
+    #[test]
+    fn errors_when_exhausted() {
+        let mut watcher = watcher(vec![reading(50, ChargingState::Discharging)]);
+
+        assert!(watcher.update().is_ok());
+        assert!(watcher.update().is_err());
+    }
+
+    /// Should extrapolate the remaining time from the slope.
+    #[test]
+    fn estimate_extrapolates() {
+        let mut estimator = Estimator::new(Duration::from_secs(600));
+        let start = Instant::now();
+
+        // Dropping 1% per minute, now at 49%: 49 minutes to empty.
+        estimator.push(start, 50);
+        estimator.push(start + Duration::from_secs(60), 49);
+
+        let remaining = estimator.estimate(ChargingState::Discharging).unwrap();
+        assert_eq!(remaining, Duration::from_secs(49 * 60));
+
+        // A discharging slope gives no charging estimate.
+        assert!(estimator.estimate(ChargingState::Charging).is_none());
+    }
+
+    /// Should emit no estimate when the slope is flat or points the wrong way.
+    #[test]
+    fn estimate_guards_bad_slope() {
+        let mut estimator = Estimator::new(Duration::from_secs(600));
+        let start = Instant::now();
+
+        estimator.push(start, 50);
+        estimator.push(start + Duration::from_secs(60), 50);
+        assert!(estimator.estimate(ChargingState::Discharging).is_none());
+
+        // Charge rising while the state says discharging: wrong-signed slope.
+        estimator.push(start + Duration::from_secs(120), 60);
+        assert!(estimator.estimate(ChargingState::Discharging).is_none());
+    }
+
+    /// Should discard samples older than `max_age`.
+    #[test]
+    fn estimate_discards_stale() {
+        let mut estimator = Estimator::new(Duration::from_secs(100));
+        let start = Instant::now();
+
+        estimator.push(start, 80);
+        estimator.push(start + Duration::from_secs(200), 40);
+
+        // Only the latest sample survives, so there is nothing to slope against.
+        assert!(estimator.estimate(ChargingState::Discharging).is_none());
+    }
+}
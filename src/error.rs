@@ -9,6 +9,7 @@ pub enum Error {
     Io(io::Error),
     Parse(num::ParseIntError),
     Validation(ValidationError),
+    Config(String),
 }
 
 /// An error when validating data.
@@ -24,6 +25,7 @@ impl fmt::Display for Error {
             Self::Io(e) => write!(f, "I/O Error: {e}"),
             Self::Parse(e) => write!(f, "Parsing Error: {e}"),
             Self::Validation(e) => write!(f, "Validation Error: {e}"),
+            Self::Config(e) => write!(f, "Config Error: {e}"),
         }
     }
 }
@@ -59,6 +61,12 @@ impl From<ValidationError> for Error {
     }
 }
 
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Config(e.to_string())
+    }
+}
+
 /// Exits a function early with an `Error`.
 #[macro_export]
 macro_rules! bail {
@@ -6,12 +6,11 @@ mod validate;
 use std::time::Duration;
 
 use battery::percentage::Breakpoints;
-use battery::watcher::{Config, Watcher};
+use battery::watcher::{Config, SysfsSource, Watcher};
 
 impl Default for Config {
     fn default() -> Self {
         Config::new(
-            String::from("/sys/class/power_supply/BAT0/capacity"),
             Breakpoints::new(10, 13, 94, 97).unwrap(),
             Duration::from_secs(45),
         )
@@ -20,7 +19,22 @@ impl Default for Config {
 }
 
 fn main() {
-    let watcher = Watcher::new(Config::default());
+    // An optional path to a TOML config file overrides the built-in defaults.
+    let (config, source) = match std::env::args().nth(1) {
+        Some(path) => match battery::watcher::load(&path) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        },
+        None => (
+            Config::default(),
+            SysfsSource::new(String::from("/sys/class/power_supply/BAT0/capacity")).unwrap(),
+        ),
+    };
+
+    let watcher = Watcher::new(config, source);
 
     for notification in watcher.run() {
         notification.show().unwrap();
@@ -16,3 +16,37 @@ pub fn on_interval(interval: Duration, immediate: bool) -> impl iter::Iterator<I
         }
     })
 }
+
+/// Formats a `Duration` as a compact `HhMm` string, e.g. `1h23m` or `45m`.
+pub fn format_duration(duration: Duration) -> String {
+    let total_minutes = duration.as_secs() / 60;
+    let hours = total_minutes / 60;
+    let minutes = total_minutes % 60;
+
+    if hours > 0 {
+        format!("{hours}h{minutes:02}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+#[cfg(test)]
+mod format_duration_tests {
+    use std::time::Duration;
+
+    use super::format_duration;
+
+    #[test]
+    fn formats() {
+        let entries = [
+            (Duration::from_secs(0), "0m"),
+            (Duration::from_secs(45 * 60), "45m"),
+            (Duration::from_secs(60 * 60), "1h00m"),
+            (Duration::from_secs(83 * 60), "1h23m"),
+        ];
+
+        for (duration, expected) in entries {
+            assert_eq!(format_duration(duration), expected);
+        }
+    }
+}
@@ -179,6 +179,32 @@ pub fn required(name: &str, val: String) -> ValidationResult<String> {
     Ok(clean.to_string())
 }
 
+pub fn not_empty<T>(name: &str, vals: &[T]) -> ValidationResult {
+    ensure!(
+        !vals.is_empty(),
+        ValidationError::Required(name.to_string())
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod not_empty_tests {
+    use super::not_empty;
+
+    #[test]
+    fn is_ok() {
+        assert!(not_empty("test_field", &[1]).is_ok());
+        assert!(not_empty("test_field", &[1, 2, 3]).is_ok());
+    }
+
+    #[test]
+    fn is_err() {
+        let empty: [u8; 0] = [];
+        assert!(not_empty("test_field", &empty).is_err());
+    }
+}
+
 #[cfg(test)]
 mod required_tests {
     use super::required;